@@ -0,0 +1,547 @@
+#[cfg(feature = "percent-encode")]
+use url::percent_encoding::{USERINFO_ENCODE_SET, percent_encode, percent_decode};
+
+use {Cookie, CookieJar};
+
+/// A jar that a [`ChildJar`](struct.ChildJar.html) can be attached to.
+///
+/// This trait captures the operations a child jar delegates to its parent. It
+/// is implemented by [`CookieJar`](struct.CookieJar.html), by `ChildJar`,
+/// `PrivateJar`, and `SignedJar`, and by mutable references to any of these,
+/// so that child jars can be stacked on top of one another. `PrivateJar` and
+/// `SignedJar` can themselves be attached to any `ParentJar`.
+/// [`TransformJar`](struct.TransformJar.html) does not implement this trait
+/// since adding a cookie to it can fail.
+pub trait ParentJar {
+    /// Returns the cookie with the name `name` as seen through this jar, or
+    /// `None` if no such cookie exists or it cannot be read.
+    fn get(&self, name: &str) -> Option<Cookie<'static>>;
+
+    /// Adds `cookie` to this jar.
+    fn add(&mut self, cookie: Cookie<'static>);
+
+    /// Adds an "original" `cookie` to this jar.
+    fn add_original(&mut self, cookie: Cookie<'static>);
+
+    /// Removes `cookie` from this jar.
+    fn remove(&mut self, cookie: Cookie<'static>);
+}
+
+impl ParentJar for CookieJar {
+    fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        CookieJar::get(self, name).cloned()
+    }
+
+    fn add(&mut self, cookie: Cookie<'static>) {
+        CookieJar::add(self, cookie)
+    }
+
+    fn add_original(&mut self, cookie: Cookie<'static>) {
+        CookieJar::add_original(self, cookie)
+    }
+
+    fn remove(&mut self, cookie: Cookie<'static>) {
+        CookieJar::remove(self, cookie)
+    }
+}
+
+impl<P: ParentJar + ?Sized> ParentJar for &mut P {
+    fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        (**self).get(name)
+    }
+
+    fn add(&mut self, cookie: Cookie<'static>) {
+        (**self).add(cookie)
+    }
+
+    fn add_original(&mut self, cookie: Cookie<'static>) {
+        (**self).add_original(cookie)
+    }
+
+    fn remove(&mut self, cookie: Cookie<'static>) {
+        (**self).remove(cookie)
+    }
+}
+
+/// A transformation applied to cookies by a [`ChildJar`](struct.ChildJar.html).
+///
+/// `encode` is applied to cookies on their way into the parent jar and
+/// `decode` to cookies on their way out. `decode` should invert `encode`,
+/// returning `None` if the cookie cannot be decoded.
+///
+/// Both methods must preserve the cookie's name, path, and domain. A
+/// `ChildJar` looks cookies up in its parent by the name given to `get`, and
+/// `remove` passes the caller's cookie to the parent untransformed, so a
+/// transform that renames a cookie, or changes its path or domain, makes the
+/// cookie impossible to retrieve or remove through the child jar.
+pub trait Transform {
+    /// Transforms `cookie` before it is stored in the parent jar. The cookie's
+    /// name, path, and domain must be left unchanged.
+    fn encode(&self, cookie: Cookie<'static>) -> Cookie<'static>;
+
+    /// Reverses `encode` on a `cookie` retrieved from the parent jar. Returns
+    /// `None` if `cookie` cannot be decoded. The cookie's name, path, and
+    /// domain must be left unchanged.
+    fn decode(&self, cookie: Cookie<'static>) -> Option<Cookie<'static>>;
+}
+
+/// A child cookie jar that applies a custom [`Transform`](trait.Transform.html)
+/// to its cookies.
+///
+/// A `ChildJar` encodes all the cookies added to it before handing them to its
+/// parent and decodes cookies retrieved from its parent. It is the building
+/// block for jars with custom encodings: implement `Transform` and attach it to
+/// any [`ParentJar`](trait.ParentJar.html), including another child jar.
+///
+/// # Example
+///
+/// ```rust
+/// use cookie::{Cookie, CookieJar, Transform};
+///
+/// struct Reverse;
+///
+/// impl Transform for Reverse {
+///     fn encode(&self, mut cookie: Cookie<'static>) -> Cookie<'static> {
+///         let value: String = cookie.value().chars().rev().collect();
+///         cookie.set_value(value);
+///         cookie
+///     }
+///
+///     fn decode(&self, cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+///         Some(self.encode(cookie))
+///     }
+/// }
+///
+/// let mut jar = CookieJar::new();
+/// jar.child(Reverse).add(Cookie::new("name", "value"));
+///
+/// assert_eq!(jar.get("name").unwrap().value(), "eulav");
+/// assert_eq!(jar.child(Reverse).get("name").unwrap().value(), "value");
+/// ```
+pub struct ChildJar<P, T> {
+    parent: P,
+    transform: T,
+}
+
+impl<P: ParentJar, T: Transform> ChildJar<P, T> {
+    /// Creates a new `ChildJar` with parent `parent` that applies `transform`
+    /// to its cookies. To attach a child jar directly to a `CookieJar`, the
+    /// [child](struct.CookieJar.html#method.child) method of `CookieJar` can
+    /// be used instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cookie::{Cookie, CookieJar, Transform};
+    /// # struct Reverse;
+    /// # impl Transform for Reverse {
+    /// #     fn encode(&self, mut cookie: Cookie<'static>) -> Cookie<'static> {
+    /// #         let value: String = cookie.value().chars().rev().collect();
+    /// #         cookie.set_value(value);
+    /// #         cookie
+    /// #     }
+    /// #     fn decode(&self, cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+    /// #         Some(self.encode(cookie))
+    /// #     }
+    /// # }
+    /// use cookie::ChildJar;
+    ///
+    /// let mut jar = CookieJar::new();
+    ///
+    /// // Stack two child jars: values are reversed twice on the way in.
+    /// let mut child = ChildJar::new(ChildJar::new(&mut jar, Reverse), Reverse);
+    /// child.add(Cookie::new("name", "value"));
+    /// assert_eq!(child.get("name").unwrap().value(), "value");
+    /// assert_eq!(jar.get("name").unwrap().value(), "value");
+    /// ```
+    pub fn new(parent: P, transform: T) -> ChildJar<P, T> {
+        ChildJar { parent, transform }
+    }
+
+    /// Returns the cookie inside this jar with the name `name`, decoded by this
+    /// jar's transform. If the cookie cannot be found, or the cookie fails to
+    /// decode, `None` is returned.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.parent.get(name).and_then(|cookie| self.transform.decode(cookie))
+    }
+
+    /// Encodes `cookie` with this jar's transform and adds it to the parent
+    /// jar.
+    pub fn add(&mut self, cookie: Cookie<'static>) {
+        let cookie = self.transform.encode(cookie);
+        self.parent.add(cookie);
+    }
+
+    /// Encodes `cookie` with this jar's transform and adds it to the parent jar
+    /// as an "original" cookie. Adding an original cookie does not affect the
+    /// [`CookieJar::delta()`](struct.CookieJar.html#method.delta) computation.
+    ///
+    /// For accurate `delta` computations, this method should not be called
+    /// after calling `remove`.
+    pub fn add_original(&mut self, cookie: Cookie<'static>) {
+        let cookie = self.transform.encode(cookie);
+        self.parent.add_original(cookie);
+    }
+
+    /// Removes `cookie` from the parent jar.
+    ///
+    /// For correct removal, the passed in `cookie` must contain the same `path`
+    /// and `domain` as the cookie that was initially set.
+    ///
+    /// See [CookieJar::remove](struct.CookieJar.html#method.remove) for more
+    /// details.
+    pub fn remove(&mut self, cookie: Cookie<'static>) {
+        self.parent.remove(cookie);
+    }
+}
+
+impl<P: ParentJar, T: Transform> ParentJar for ChildJar<P, T> {
+    fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        ChildJar::get(self, name)
+    }
+
+    fn add(&mut self, cookie: Cookie<'static>) {
+        ChildJar::add(self, cookie)
+    }
+
+    fn add_original(&mut self, cookie: Cookie<'static>) {
+        ChildJar::add_original(self, cookie)
+    }
+
+    fn remove(&mut self, cookie: Cookie<'static>) {
+        ChildJar::remove(self, cookie)
+    }
+}
+
+/// A child cookie jar that applies a pair of fallible closures to the values of
+/// its cookies.
+///
+/// A `TransformJar` is typically obtained via the
+/// [transform](struct.CookieJar.html#method.transform) method of `CookieJar`.
+/// The `encode` closure is applied to the value of every cookie added to the
+/// jar, and the `decode` closure to the value of every cookie retrieved from
+/// it. Unlike a [`ChildJar`](struct.ChildJar.html), adding a cookie can fail:
+/// if `encode` returns an error, the cookie is not added and the error is
+/// returned.
+///
+/// Because adding can fail, a `TransformJar` does not implement
+/// [`ParentJar`](trait.ParentJar.html): it can be attached on top of any other
+/// jar, but no other child jar can be attached on top of it. For a transform
+/// that needs to sit below other child jars, implement
+/// [`Transform`](trait.Transform.html) and use a `ChildJar` instead.
+pub struct TransformJar<P, E, D> {
+    parent: P,
+    encode: E,
+    decode: D,
+}
+
+impl<P: ParentJar, E, D> TransformJar<P, E, D> {
+    /// Creates a new `TransformJar` with parent `parent` that encodes values
+    /// with `encode` and decodes them with `decode`. This method is typically
+    /// called indirectly via the `transform` method of `CookieJar`.
+    pub fn new<X, Y>(parent: P, encode: E, decode: D) -> TransformJar<P, E, D>
+        where E: Fn(&str) -> Result<String, X>, D: Fn(&str) -> Result<String, Y>
+    {
+        TransformJar { parent, encode, decode }
+    }
+
+    /// Returns the cookie inside this jar with the name `name`, with its value
+    /// decoded. If the cookie cannot be found, or the `decode` closure fails,
+    /// `None` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.add(Cookie::new("num", "42"));
+    /// jar.add(Cookie::new("text", "xyz"));
+    ///
+    /// let hex = jar.transform(
+    ///     |v| v.parse::<u32>().map(|n| format!("{:x}", n)),
+    ///     |v| u32::from_str_radix(v, 16).map(|n| n.to_string()));
+    ///
+    /// assert_eq!(hex.get("num").unwrap().value(), "66");
+    /// assert!(hex.get("text").is_none());
+    /// ```
+    pub fn get<X>(&self, name: &str) -> Option<Cookie<'static>>
+        where D: Fn(&str) -> Result<String, X>
+    {
+        let mut cookie = self.parent.get(name)?;
+        let value = (self.decode)(cookie.value()).ok()?;
+        cookie.set_value(value);
+        Some(cookie)
+    }
+
+    /// Encodes the value of `cookie` and adds it to the parent jar. If the
+    /// `encode` closure fails, nothing is added and its error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// {
+    ///     let mut hex = jar.transform(
+    ///         |v| v.parse::<u32>().map(|n| format!("{:x}", n)),
+    ///         |v| u32::from_str_radix(v, 16).map(|n| n.to_string()));
+    ///
+    ///     assert!(hex.add(Cookie::new("num", "255")).is_ok());
+    ///     assert!(hex.add(Cookie::new("text", "abc")).is_err());
+    /// }
+    ///
+    /// assert_eq!(jar.get("num").unwrap().value(), "ff");
+    /// assert!(jar.get("text").is_none());
+    /// ```
+    pub fn add<X>(&mut self, mut cookie: Cookie<'static>) -> Result<(), X>
+        where E: Fn(&str) -> Result<String, X>
+    {
+        let value = (self.encode)(cookie.value())?;
+        cookie.set_value(value);
+        self.parent.add(cookie);
+        Ok(())
+    }
+
+    /// Encodes the value of `cookie` and adds it to the parent jar as an
+    /// "original" cookie. If the `encode` closure fails, nothing is added and
+    /// its error is returned. Adding an original cookie does not affect the
+    /// [`CookieJar::delta()`](struct.CookieJar.html#method.delta) computation.
+    ///
+    /// For accurate `delta` computations, this method should not be called
+    /// after calling `remove`.
+    pub fn add_original<X>(&mut self, mut cookie: Cookie<'static>) -> Result<(), X>
+        where E: Fn(&str) -> Result<String, X>
+    {
+        let value = (self.encode)(cookie.value())?;
+        cookie.set_value(value);
+        self.parent.add_original(cookie);
+        Ok(())
+    }
+
+    /// Removes `cookie` from the parent jar.
+    ///
+    /// For correct removal, the passed in `cookie` must contain the same `path`
+    /// and `domain` as the cookie that was initially set.
+    ///
+    /// See [CookieJar::remove](struct.CookieJar.html#method.remove) for more
+    /// details.
+    pub fn remove(&mut self, cookie: Cookie<'static>) {
+        self.parent.remove(cookie);
+    }
+}
+
+#[cfg(feature = "percent-encode")]
+define_encode_set! {
+    /// The encode set used by `PercentEncode`: the `userinfo` encode set plus
+    /// `%`, so that encoded values decode back to themselves, and `+`, which
+    /// form decoders such as PHP's `urldecode` read as a space.
+    pub VALUE_ENCODE_SET = [USERINFO_ENCODE_SET] | {'%', '+'}
+}
+
+/// A [`Transform`](trait.Transform.html) that percent-encodes cookie values.
+///
+/// Values are percent-encoded with the encode set used by
+/// [`Cookie::encoded`](struct.Cookie.html#method.encoded), extended with `%`
+/// and `+`, and percent-decoded on retrieval. As with PHP's `urldecode`, a `+`
+/// in a retrieved value decodes to a space; since `encode` escapes a literal
+/// `+` as `%2B`, this is lossless for values written through this transform.
+/// Decoding fails if the decoded value is not valid UTF-8.
+///
+/// This type is only available when the `percent-encode` feature is enabled.
+#[cfg(feature = "percent-encode")]
+#[derive(Debug, Clone, Copy)]
+pub struct PercentEncode;
+
+#[cfg(feature = "percent-encode")]
+impl Transform for PercentEncode {
+    fn encode(&self, mut cookie: Cookie<'static>) -> Cookie<'static> {
+        let value = percent_encode(cookie.value().as_bytes(), VALUE_ENCODE_SET).to_string();
+        cookie.set_value(value);
+        cookie
+    }
+
+    fn decode(&self, mut cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+        let bytes: Vec<u8> = cookie.value().bytes()
+            .map(|b| if b == b'+' { b' ' } else { b })
+            .collect();
+
+        let value = percent_decode(&bytes).decode_utf8().ok()?.into_owned();
+        cookie.set_value(value);
+        Some(cookie)
+    }
+}
+
+/// A child cookie jar that percent-encodes the values of its cookies.
+///
+/// An _encoded_ child jar percent-encodes the values of all the cookies added
+/// to it and percent-decodes the values of cookies retrieved from it. This
+/// matches the behavior of frameworks that URL-encode cookie values by default.
+/// An `EncodedJar` is obtained via the
+/// [encoded](struct.CookieJar.html#method.encoded) method of `CookieJar`.
+///
+/// This type is only available when the `percent-encode` feature is enabled.
+#[cfg(feature = "percent-encode")]
+pub type EncodedJar<'a> = ChildJar<&'a mut CookieJar, PercentEncode>;
+
+#[cfg(test)]
+mod test {
+    use super::{ChildJar, Transform, TransformJar};
+    use {Cookie, CookieJar};
+
+    struct Prefix(&'static str);
+
+    impl Transform for Prefix {
+        fn encode(&self, mut cookie: Cookie<'static>) -> Cookie<'static> {
+            let value = format!("{}{}", self.0, cookie.value());
+            cookie.set_value(value);
+            cookie
+        }
+
+        fn decode(&self, mut cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+            if !cookie.value().starts_with(self.0) {
+                return None;
+            }
+
+            let value = cookie.value()[self.0.len()..].to_string();
+            cookie.set_value(value);
+            Some(cookie)
+        }
+    }
+
+    #[test]
+    fn simple() {
+        let mut jar = CookieJar::new();
+        jar.child(Prefix("p:")).add(Cookie::new("name", "val"));
+        assert_eq!(jar.get("name").unwrap().value(), "p:val");
+        assert_eq!(jar.child(Prefix("p:")).get("name").unwrap().value(), "val");
+
+        jar.child(Prefix("p:")).remove(Cookie::named("name"));
+        assert!(jar.child(Prefix("p:")).get("name").is_none());
+        assert_eq!(jar.iter().count(), 0);
+    }
+
+    #[test]
+    fn undecodable() {
+        let mut jar = CookieJar::new();
+        jar.add(Cookie::new("plain", "value"));
+        assert!(jar.child(Prefix("p:")).get("plain").is_none());
+        assert!(jar.get("plain").is_some());
+    }
+
+    #[test]
+    fn nested() {
+        let mut jar = CookieJar::new();
+        ChildJar::new(jar.child(Prefix("a:")), Prefix("b:"))
+            .add(Cookie::new("name", "value"));
+        assert_eq!(jar.get("name").unwrap().value(), "a:b:value");
+        assert_eq!(jar.child(Prefix("a:")).get("name").unwrap().value(), "b:value");
+
+        jar.child(Prefix("a:")).add_original(Cookie::new("orig", "x"));
+        assert_eq!(jar.delta().count(), 1);
+
+        let child = ChildJar::new(jar.child(Prefix("a:")), Prefix("b:"));
+        assert_eq!(child.get("name").unwrap().value(), "value");
+        assert!(child.get("orig").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "secure")]
+    fn over_private() {
+        let key = ::Key::generate();
+        let mut jar = CookieJar::new();
+        ChildJar::new(jar.private(&key), Prefix("p:"))
+            .add(Cookie::new("name", "value"));
+
+        assert_eq!(jar.private(&key).get("name").unwrap().value(), "p:value");
+        let child = ChildJar::new(jar.private(&key), Prefix("p:"));
+        assert_eq!(child.get("name").unwrap().value(), "value");
+    }
+
+    #[test]
+    #[cfg(feature = "secure")]
+    fn under_private() {
+        let key = ::Key::generate();
+        let mut jar = CookieJar::new();
+        {
+            let mut child = jar.child(Prefix("p:"));
+            ::PrivateJar::new(&mut child, &key).add(Cookie::new("name", "value"));
+            ::SignedJar::new(&mut child, &key).add(Cookie::new("signed", "value"));
+
+            assert_eq!(::PrivateJar::new(&mut child, &key).get("name").unwrap().value(), "value");
+            assert_eq!(::SignedJar::new(&mut child, &key).get("signed").unwrap().value(), "value");
+            assert!(child.get("signed").unwrap().value().ends_with("value"));
+        }
+
+        assert!(jar.get("name").unwrap().value().starts_with("p:"));
+        assert!(jar.private(&key).get("name").is_none());
+    }
+
+    #[test]
+    fn transform() {
+        let encode = |v: &str| if v.is_empty() { Err("empty") } else { Ok(v.to_uppercase()) };
+        let decode = |v: &str| if v.starts_with('!') { Err(()) } else { Ok(v.to_lowercase()) };
+
+        let mut jar = CookieJar::new();
+        jar.transform(encode, decode).add(Cookie::new("name", "val")).unwrap();
+        assert_eq!(jar.get("name").unwrap().value(), "VAL");
+        assert_eq!(jar.transform(encode, decode).get("name").unwrap().value(), "val");
+
+        let empty = jar.transform(encode, decode).add_original(Cookie::named("empty"));
+        assert_eq!(empty, Err("empty"));
+        assert!(jar.get("empty").is_none());
+
+        jar.add(Cookie::new("bad", "!x"));
+        assert!(jar.transform(encode, decode).get("bad").is_none());
+
+        let mut child = TransformJar::new(jar.child(Prefix("p:")), encode, decode);
+        child.add(Cookie::new("nested", "val")).unwrap();
+        assert_eq!(child.get("nested").unwrap().value(), "val");
+        child.remove(Cookie::named("nested"));
+        assert!(child.get("nested").is_none());
+    }
+
+    #[test]
+    fn transform_inferred() {
+        let mut jar = CookieJar::new();
+        jar.transform(|v| Ok::<String, ()>(v.to_uppercase()), |v| Ok::<String, ()>(v.to_lowercase()))
+            .add(Cookie::new("name", "val"))
+            .unwrap();
+        assert_eq!(jar.get("name").unwrap().value(), "VAL");
+
+        let upper = jar.transform(|v| Ok::<_, ()>(v.to_uppercase()), |v| Ok::<_, ()>(v.to_lowercase()));
+        assert_eq!(upper.get("name").unwrap().value(), "val");
+    }
+
+    #[test]
+    #[cfg(feature = "percent-encode")]
+    fn encoded() {
+        let mut jar = CookieJar::new();
+        jar.encoded().add(Cookie::new("name", "a b;c"));
+        assert_eq!(jar.get("name").unwrap().value(), "a%20b%3Bc");
+        assert_eq!(jar.encoded().get("name").unwrap().value(), "a b;c");
+
+        jar.add_original(Cookie::new("php", "x%2By%3Dz"));
+        assert_eq!(jar.encoded().get("php").unwrap().value(), "x+y=z");
+
+        jar.add_original(Cookie::new("form", "hello+world%21"));
+        assert_eq!(jar.encoded().get("form").unwrap().value(), "hello world!");
+
+        jar.add(Cookie::new("bad", "%FF"));
+        assert!(jar.encoded().get("bad").is_none());
+
+        jar.encoded().add(Cookie::new("percent", "100%41 %"));
+        assert_eq!(jar.get("percent").unwrap().value(), "100%2541%20%25");
+        assert_eq!(jar.encoded().get("percent").unwrap().value(), "100%41 %");
+
+        jar.encoded().add(Cookie::new("plus", "a+b"));
+        assert_eq!(jar.get("plus").unwrap().value(), "a%2Bb");
+        assert_eq!(jar.encoded().get("plus").unwrap().value(), "a+b");
+
+        let key_value = "unchanged";
+        jar.encoded().add_original(Cookie::new("plain", key_value));
+        assert_eq!(jar.get("plain").unwrap().value(), key_value);
+        assert_eq!(jar.delta().count(), 4);
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::fmt::Write;
 use std::mem::replace;
 
 use time::{self, Duration};
@@ -6,7 +7,43 @@ use time::{self, Duration};
 #[cfg(feature = "secure")]
 use secure::{PrivateJar, SignedJar, Key};
 use delta::DeltaCookie;
-use Cookie;
+use child::{ChildJar, Transform, TransformJar};
+#[cfg(feature = "percent-encode")]
+use child::{EncodedJar, PercentEncode};
+use write::SliceWriter;
+use {BufferTooSmall, Cookie};
+
+/// The policy used by
+/// [`add_original_deduped`](struct.CookieJar.html#method.add_original_deduped)
+/// to pick which of several cookies with the same name is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupePolicy {
+    /// Keep the cookie received first. RFC 6265 Section 5.4 says clients
+    /// should list cookies with longer paths first, so for clients that follow
+    /// this recommendation, this keeps the most specific cookie.
+    First,
+    /// Keep the cookie with the longest `Path`, where a missing `Path` counts
+    /// as empty. Among cookies with equally long paths, the first is kept.
+    ///
+    /// Cookies parsed from a request's `Cookie` header never carry a `Path`,
+    /// so for such cookies this policy behaves exactly like `First`. It is only
+    /// useful for cookies whose path is known, e.g. cookies built or parsed
+    /// from `Set-Cookie` values.
+    LongestPath,
+}
+
+impl DedupePolicy {
+    /// Returns `true` if `new` should replace the previously received `old`.
+    fn prefers(&self, new: &Cookie, old: &Cookie) -> bool {
+        match *self {
+            DedupePolicy::First => false,
+            DedupePolicy::LongestPath => {
+                let path_len = |c: &Cookie| c.path().map_or(0, |p| p.len());
+                path_len(new) > path_len(old)
+            }
+        }
+    }
+}
 
 /// A collection of cookies that tracks its modifications.
 ///
@@ -150,6 +187,65 @@ impl CookieJar {
         self.original_cookies.replace(DeltaCookie::added(cookie));
     }
 
+    /// Adds each cookie in `cookies` as an "original" cookie, keeping only one
+    /// original cookie per name as chosen by `policy`, and returns the cookies
+    /// that were dropped. An original cookie already in the jar counts as
+    /// having been received before any cookie in `cookies`.
+    ///
+    /// Unlike [`add_original`](#method.add_original), which always keeps the
+    /// last cookie with a given name, this method lets the caller decide which
+    /// of several same-named cookies shadows the others. Such duplicates arise
+    /// when a client holds cookies with the same name for different paths, or
+    /// when a buggy client repeats a cookie.
+    ///
+    /// A request's `Cookie` header does not include the paths of the cookies
+    /// it carries, so duplicates for different paths cannot be told apart by
+    /// path once received. For such cookies, `DedupePolicy::LongestPath` keeps
+    /// the same cookie as `DedupePolicy::First`, which relies on the client
+    /// listing cookies with longer paths first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{CookieJar, Cookie, DedupePolicy};
+    ///
+    /// let received = vec![
+    ///     Cookie::new("session", "specific"),
+    ///     Cookie::new("theme", "dark"),
+    ///     Cookie::new("session", "general"),
+    /// ];
+    ///
+    /// let mut jar = CookieJar::new();
+    /// let dropped = jar.add_original_deduped(received, DedupePolicy::First);
+    ///
+    /// assert_eq!(jar.get("session").map(|c| c.value()), Some("specific"));
+    /// assert_eq!(jar.iter().count(), 2);
+    /// assert_eq!(jar.delta().count(), 0);
+    ///
+    /// assert_eq!(dropped.len(), 1);
+    /// assert_eq!(dropped[0].value(), "general");
+    /// ```
+    pub fn add_original_deduped<I>(&mut self, cookies: I, policy: DedupePolicy)
+        -> Vec<Cookie<'static>>
+        where I: IntoIterator<Item = Cookie<'static>>
+    {
+        let mut dropped = vec![];
+        for cookie in cookies {
+            let keep = match self.original_cookies.get(cookie.name()) {
+                Some(existing) => policy.prefers(&cookie, existing),
+                None => true,
+            };
+
+            if !keep {
+                dropped.push(cookie);
+            } else if let Some(old) = self.original_cookies.replace(DeltaCookie::added(cookie)) {
+                dropped.push(old.cookie);
+            }
+        }
+
+        dropped
+    }
+
     /// Adds `cookie` to this jar. If a cookie with the same name already
     /// exists, it is replaced with `cookie`.
     ///
@@ -273,6 +369,59 @@ impl CookieJar {
         self.delta_cookies.remove(cookie.name());
     }
 
+    /// Removes every _original_ cookie from this jar, queueing a _removal_
+    /// cookie for each in the `delta`, and discards any cookies added since.
+    /// Each removal cookie uses the `path` and `domain` of the original cookie
+    /// it removes.
+    ///
+    /// Cookies received from a client's HTTP message do not carry their `path`
+    /// or `domain`, so a removal cookie may not match the cookie stored on the
+    /// client. To wipe all of a client's cookies for the origin regardless,
+    /// also send a `Clear-Site-Data` header with the value returned by
+    /// [`clear_site_data`](#method.clear_site_data).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{CookieJar, Cookie};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.add_original(Cookie::new("name", "value"));
+    /// jar.add_original(Cookie::build("second", "two").path("/").finish());
+    /// jar.add(Cookie::new("new", "third"));
+    ///
+    /// jar.remove_all();
+    /// assert_eq!(jar.iter().count(), 0);
+    ///
+    /// // The delta contains a removal cookie for each original cookie.
+    /// assert_eq!(jar.delta().count(), 2);
+    /// assert!(jar.delta().all(|c| c.value().is_empty()));
+    /// ```
+    pub fn remove_all(&mut self) {
+        let originals: Vec<_> = self.original_cookies.iter()
+            .map(|delta| delta.cookie.clone())
+            .collect();
+
+        self.delta_cookies.clear();
+        for cookie in originals {
+            self.remove(cookie);
+        }
+    }
+
+    /// Returns the value of a `Clear-Site-Data` header that instructs the
+    /// client to remove all of its cookies for the origin of the response.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::CookieJar;
+    ///
+    /// assert_eq!(CookieJar::clear_site_data(), "\"cookies\"");
+    /// ```
+    pub fn clear_site_data() -> &'static str {
+        "\"cookies\""
+    }
+
     /// Removes all cookies from this cookie jar.
     #[deprecated(since = "0.7.0", note = "calling this method may not remove \
                  all cookies since the path and domain are not specified; use \
@@ -313,6 +462,38 @@ impl CookieJar {
         Delta { iter: self.delta_cookies.iter() }
     }
 
+    /// Renders the [delta](#method.delta) of this jar directly into `buf` as a
+    /// sequence of `Set-Cookie` header lines, each terminated by `\r\n`, and
+    /// returns the number of bytes written. No allocation is performed.
+    ///
+    /// If the rendered delta does not fit in `buf`, returns `BufferTooSmall`.
+    /// The contents of `buf` are unspecified in this case.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{CookieJar, Cookie};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.add_original(Cookie::new("name", "value"));
+    /// jar.add(Cookie::new("new", "third"));
+    ///
+    /// let mut buf = [0u8; 128];
+    /// let len = jar.write_delta_to(&mut buf).unwrap();
+    /// assert_eq!(&buf[..len], &b"Set-Cookie: new=third\r\n"[..]);
+    ///
+    /// let mut small = [0u8; 8];
+    /// assert!(jar.write_delta_to(&mut small).is_err());
+    /// ```
+    pub fn write_delta_to(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let mut writer = SliceWriter::new(buf);
+        for cookie in self.delta() {
+            write!(writer, "Set-Cookie: {}\r\n", cookie).map_err(|_| BufferTooSmall)?;
+        }
+
+        Ok(writer.written())
+    }
+
     /// Returns an iterator over all of the cookies present in this jar.
     ///
     /// # Example
@@ -350,6 +531,102 @@ impl CookieJar {
         }
     }
 
+    /// Returns a `ChildJar` with `self` as its parent jar that applies
+    /// `transform` to cookies added/retrieved from the child jar.
+    ///
+    /// Any modifications to the child jar will be reflected on the parent jar,
+    /// and any retrievals from the child jar will be made from the parent jar.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar, Transform};
+    ///
+    /// struct Upper;
+    ///
+    /// impl Transform for Upper {
+    ///     fn encode(&self, mut cookie: Cookie<'static>) -> Cookie<'static> {
+    ///         let value = cookie.value().to_uppercase();
+    ///         cookie.set_value(value);
+    ///         cookie
+    ///     }
+    ///
+    ///     fn decode(&self, cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+    ///         Some(cookie)
+    ///     }
+    /// }
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.child(Upper).add(Cookie::new("name", "value"));
+    /// assert_eq!(jar.get("name").unwrap().value(), "VALUE");
+    /// ```
+    pub fn child<T: Transform>(&mut self, transform: T) -> ChildJar<&mut CookieJar, T> {
+        ChildJar::new(self, transform)
+    }
+
+    /// Returns a `TransformJar` with `self` as its parent jar that encodes the
+    /// values of cookies added to the child jar with `encode` and decodes the
+    /// values of cookies retrieved from the child jar with `decode`.
+    ///
+    /// Both closures are fallible. A failure to encode is returned from the
+    /// child jar's `add` methods; a failure to decode causes the child jar's
+    /// `get` to return `None`. For reusable transformations, implement
+    /// [`Transform`](trait.Transform.html) and use [child](#method.child).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar};
+    ///
+    /// fn rot13(value: &str) -> Result<String, ()> {
+    ///     Ok(value.chars().map(|c| match c {
+    ///         'a'..='m' | 'A'..='M' => (c as u8 + 13) as char,
+    ///         'n'..='z' | 'N'..='Z' => (c as u8 - 13) as char,
+    ///         _ => c,
+    ///     }).collect())
+    /// }
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.transform(rot13, rot13).add(Cookie::new("name", "value")).unwrap();
+    ///
+    /// assert_eq!(jar.get("name").unwrap().value(), "inyhr");
+    /// assert_eq!(jar.transform(rot13, rot13).get("name").unwrap().value(), "value");
+    /// ```
+    pub fn transform<E, D, X, Y>(&mut self, encode: E, decode: D) -> TransformJar<&mut CookieJar, E, D>
+        where E: Fn(&str) -> Result<String, X>, D: Fn(&str) -> Result<String, Y>
+    {
+        TransformJar::new(self, encode, decode)
+    }
+
+    /// Returns an `EncodedJar` with `self` as its parent jar that
+    /// percent-encodes the values of cookies added to the child jar and
+    /// percent-decodes the values of cookies retrieved from the child jar.
+    ///
+    /// Any modifications to the child jar will be reflected on the parent jar,
+    /// and any retrievals from the child jar will be made from the parent jar.
+    ///
+    /// This method is only available when the `percent-encode` feature is
+    /// enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.encoded().add(Cookie::new("name", "this; value?"));
+    ///
+    /// // The cookie's value is percent-encoded.
+    /// assert_eq!(jar.get("name").unwrap().value(), "this%3B%20value%3F");
+    ///
+    /// // It is decoded through the child jar.
+    /// assert_eq!(jar.encoded().get("name").unwrap().value(), "this; value?");
+    /// ```
+    #[cfg(feature = "percent-encode")]
+    pub fn encoded(&mut self) -> EncodedJar<'_> {
+        ChildJar::new(self, PercentEncode)
+    }
+
     /// Returns a `PrivateJar` with `self` as its parent jar using the key `key`
     /// to sign/encrypt and verify/decrypt cookies added/retrieved from the
     /// child jar.
@@ -468,7 +745,7 @@ impl<'a> Iterator for Iter<'a> {
 #[cfg(test)]
 mod test {
     use super::CookieJar;
-    use Cookie;
+    use {BufferTooSmall, Cookie};
 
     #[test]
     #[allow(deprecated)]
@@ -561,6 +838,80 @@ mod test {
         assert_eq!(names.get("original").unwrap(), &Some(Duration::seconds(0)));
     }
 
+    #[test]
+    fn write_delta() {
+        let mut jar = CookieJar::new();
+        let mut buf = [0u8; 64];
+        assert_eq!(jar.write_delta_to(&mut buf), Ok(0));
+
+        jar.add(Cookie::new("a", "one"));
+        jar.add(Cookie::new("b", "two"));
+        let len = jar.write_delta_to(&mut buf).unwrap();
+        let rendered = ::std::str::from_utf8(&buf[..len]).unwrap();
+        assert_eq!(rendered.len(), 38);
+        assert!(rendered.contains("Set-Cookie: a=one\r\n"));
+        assert!(rendered.contains("Set-Cookie: b=two\r\n"));
+
+        assert_eq!(jar.write_delta_to(&mut buf[..37]), Err(BufferTooSmall));
+        assert_eq!(jar.write_delta_to(&mut buf[..38]), Ok(38));
+    }
+
+    #[test]
+    fn remove_all() {
+        let mut jar = CookieJar::new();
+        jar.remove_all();
+        assert_eq!(jar.delta().count(), 0);
+
+        jar.add_original(Cookie::build("a", "1").path("/a").domain("a.b").finish());
+        jar.add_original(Cookie::new("b", "2"));
+        jar.add(Cookie::new("b", "override"));
+        jar.add(Cookie::new("c", "3"));
+        jar.remove(Cookie::named("a"));
+
+        jar.remove_all();
+        assert_eq!(jar.iter().count(), 0);
+        assert!(jar.get("b").is_none());
+        assert_eq!(jar.delta().count(), 2);
+        assert!(jar.delta().all(|c| c.value().is_empty()));
+
+        let a = jar.delta().find(|c| c.name() == "a").unwrap();
+        assert_eq!((a.path(), a.domain()), (Some("/a"), Some("a.b")));
+        assert!(jar.delta().any(|c| c.name() == "b"));
+    }
+
+    #[test]
+    fn add_original_deduped() {
+        use super::DedupePolicy;
+
+        let received = || vec![
+            Cookie::build("a", "root").path("/").finish(),
+            Cookie::build("a", "deep").path("/x/y").finish(),
+            Cookie::new("a", "none"),
+            Cookie::build("a", "deep2").path("/x/z").finish(),
+            Cookie::new("b", "only"),
+        ];
+
+        let mut jar = CookieJar::new();
+        let dropped = jar.add_original_deduped(received(), DedupePolicy::First);
+        assert_eq!(jar.get("a").unwrap().value(), "root");
+        assert_eq!(jar.get("b").unwrap().value(), "only");
+        let values: Vec<_> = dropped.iter().map(|c| c.value()).collect();
+        assert_eq!(values, vec!["deep", "none", "deep2"]);
+
+        let mut jar = CookieJar::new();
+        let dropped = jar.add_original_deduped(received(), DedupePolicy::LongestPath);
+        assert_eq!(jar.get("a").unwrap().value(), "deep");
+        let values: Vec<_> = dropped.iter().map(|c| c.value()).collect();
+        assert_eq!(values, vec!["root", "none", "deep2"]);
+        assert_eq!(jar.iter().count(), 2);
+        assert_eq!(jar.delta().count(), 0);
+
+        let dropped = jar.add_original_deduped(vec![Cookie::new("a", "late")],
+            DedupePolicy::First);
+        assert_eq!(dropped[0].value(), "late");
+        assert_eq!(jar.get("a").unwrap().value(), "deep");
+    }
+
     #[test]
     fn replace_original() {
         let mut jar = CookieJar::new();
@@ -48,8 +48,23 @@
 //!   `Cookie` become available. The `encoded` method returns a wrapper around a
 //!   `Cookie` whose `Display` implementation percent-encodes the name and value
 //!   of the cookie. The `parse_encoded` method percent-decodes the name and
-//!   value of a `Cookie` during parsing. When this feature is disabled, the
-//!   `encoded` and `parse_encoded` methods are not available.
+//!   value of a `Cookie` during parsing. The
+//!   [encoded](struct.CookieJar.html#method.encoded) method of `CookieJar`
+//!   returns an [`EncodedJar`](type.EncodedJar.html), a child jar that
+//!   percent-encodes and decodes cookie values. When this feature is disabled,
+//!   the `encoded` and `parse_encoded` methods are not available.
+//!
+//! * **idna** (disabled by default)
+//!
+//!   Enables conversion of internationalized domain names to their ASCII
+//!   (A-label, or "punycode") form.
+//!
+//!   When this feature is enabled, a Unicode domain passed to
+//!   [`set_domain`](struct.Cookie.html#method.set_domain), such as
+//!   `bücher.example`, is stored as its A-label form, `xn--bcher-kva.example`.
+//!   The same conversion is applied to the `Domain` attribute of a parsed
+//!   cookie, so both forms are accepted on parse. When this feature is
+//!   disabled, domains are stored exactly as given.
 //!
 //! You can enable features via the `Cargo.toml` file:
 //!
@@ -61,20 +76,23 @@
 #![doc(html_root_url = "https://docs.rs/cookie/0.12")]
 #![deny(missing_docs)]
 
-#[cfg(feature = "percent-encode")] extern crate url;
+#[cfg(feature = "percent-encode")] #[macro_use] extern crate url;
+#[cfg(feature = "idna")] extern crate idna;
 extern crate time;
 
 mod builder;
 mod parse;
 mod jar;
+mod child;
 mod delta;
 mod draft;
+mod write;
 
 #[cfg(feature = "secure")] #[macro_use] mod secure;
 #[cfg(feature = "secure")] pub use secure::*;
 
 use std::borrow::Cow;
-use std::fmt;
+use std::fmt::{self, Write};
 use std::str::FromStr;
 
 #[allow(unused_imports, deprecated)]
@@ -84,11 +102,15 @@ use std::ascii::AsciiExt;
 use url::percent_encoding::{USERINFO_ENCODE_SET, percent_encode};
 use time::{Tm, Duration};
 
-use parse::parse_cookie;
-pub use parse::ParseError;
+use parse::{parse_cookie, parse_header};
+use write::SliceWriter;
+pub use parse::{ParseError, Nameless, HeaderCookies};
 pub use builder::CookieBuilder;
-pub use jar::{CookieJar, Delta, Iter};
+pub use jar::{CookieJar, Delta, Iter, DedupePolicy};
+pub use child::{ChildJar, ParentJar, Transform, TransformJar};
+#[cfg(feature = "percent-encode")] pub use child::{EncodedJar, PercentEncode};
 pub use draft::*;
+pub use write::BufferTooSmall;
 
 #[derive(Debug, Clone)]
 enum CookieStr {
@@ -117,6 +139,27 @@ impl CookieStr {
         }
     }
 
+    /// Converts `self` into the string it corresponds to. If `self` is derived
+    /// from indexes, the corresponding subslice of `string` is borrowed when
+    /// `string` is borrowed and copied otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is an indexed string and `string` is None.
+    fn into_cow<'c>(self, string: Option<&Cow<'c, str>>) -> Cow<'c, str> {
+        match self {
+            CookieStr::Indexed(i, j) => {
+                let s = string.expect("`Some` base string must exist when \
+                    converting indexed str to str! (This is a module invariant.)");
+                match *s {
+                    Cow::Borrowed(s) => Cow::Borrowed(&s[i..j]),
+                    Cow::Owned(ref s) => Cow::Owned(s[i..j].to_string()),
+                }
+            },
+            CookieStr::Concrete(cstr) => cstr,
+        }
+    }
+
     fn to_raw_str<'s, 'c: 's>(&'s self, string: &'s Cow<'c, str>) -> Option<&'c str> {
         match *self {
             CookieStr::Indexed(i, j) => {
@@ -130,6 +173,18 @@ impl CookieStr {
     }
 }
 
+/// Returns the A-label form of `domain` if `domain` is an internationalized
+/// domain name that can be converted. Returns `None` if `domain` is already
+/// ASCII or is not a valid domain name.
+#[cfg(feature = "idna")]
+fn domain_to_ascii(domain: &str) -> Option<String> {
+    if domain.is_ascii() {
+        return None;
+    }
+
+    idna::domain_to_ascii(domain).ok()
+}
+
 /// Representation of an HTTP cookie.
 ///
 /// # Constructing a `Cookie`
@@ -266,6 +321,34 @@ impl<'c> Cookie<'c> {
         parse_cookie(s, false)
     }
 
+    /// Parses every cookie in the given HTTP `Cookie` header value string,
+    /// i.e. a `;`-separated list of `name=value` pairs. Does not perform any
+    /// percent-decoding.
+    ///
+    /// Segments without a name, either `=value` or a bare `value`, are handled
+    /// according to `nameless`. See [`Nameless`](enum.Nameless.html).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cookie::{Cookie, Nameless};
+    ///
+    /// let header = "foo=bar; =baz; qux";
+    ///
+    /// let cookies: Vec<_> = Cookie::parse_header(header, Nameless::Accept)
+    ///     .map(|c| c.unwrap())
+    ///     .collect();
+    /// assert_eq!(cookies[0].name_value(), ("foo", "bar"));
+    /// assert_eq!(cookies[1].name_value(), ("", "baz"));
+    /// assert_eq!(cookies[2].name_value(), ("", "qux"));
+    ///
+    /// assert_eq!(Cookie::parse_header(header, Nameless::Skip).count(), 1);
+    /// assert!(Cookie::parse_header(header, Nameless::Reject).any(|c| c.is_err()));
+    /// ```
+    pub fn parse_header(s: &'c str, nameless: Nameless) -> HeaderCookies<'c> {
+        parse_header(s, nameless)
+    }
+
     /// Parses a `Cookie` from the given HTTP cookie header value string where
     /// the name and value fields are percent-encoded. Percent-decodes the
     /// name/value fields.
@@ -381,6 +464,32 @@ impl<'c> Cookie<'c> {
         (self.name(), self.value())
     }
 
+    /// Converts `self` into its name and value as a tuple of `(name, value)`.
+    ///
+    /// If `self` was parsed from a borrowed string, the name and value borrow
+    /// from that string. If it was parsed from an owned `String`, the name and
+    /// value are copied out of that string into newly allocated `String`s.
+    /// Names and values that were set directly are moved out of `self` without
+    /// copying.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cookie::Cookie;
+    ///
+    /// let c = Cookie::new("name", "value");
+    /// let (name, value) = c.into_pair();
+    /// assert_eq!((&*name, &*value), ("name", "value"));
+    ///
+    /// let c = Cookie::parse("foo=bar; Path=/").unwrap();
+    /// let (name, value) = c.into_pair();
+    /// assert_eq!((&*name, &*value), ("foo", "bar"));
+    /// ```
+    pub fn into_pair(self) -> (Cow<'c, str>, Cow<'c, str>) {
+        let string = self.cookie_string.as_ref();
+        (self.name.into_cow(string), self.value.into_cow(string))
+    }
+
     /// Returns whether this cookie was marked `HttpOnly` or not. Returns
     /// `Some(true)` when the cookie was explicitly set (manually or parsed) as
     /// `HttpOnly`, `Some(false)` when `http_only` was manually set to `false`,
@@ -674,6 +783,9 @@ impl<'c> Cookie<'c> {
 
     /// Sets the `domain` of `self` to `domain`.
     ///
+    /// When the `idna` feature is enabled, an internationalized `domain` is
+    /// converted to its ASCII (A-label) form.
+    ///
     /// # Example
     ///
     /// ```
@@ -686,7 +798,15 @@ impl<'c> Cookie<'c> {
     /// assert_eq!(c.domain(), Some("rust-lang.org"));
     /// ```
     pub fn set_domain<D: Into<Cow<'static, str>>>(&mut self, domain: D) {
-        self.domain = Some(CookieStr::Concrete(domain.into()));
+        let domain = domain.into();
+
+        #[cfg(feature = "idna")]
+        let domain = match domain_to_ascii(&domain) {
+            Some(ascii) => Cow::Owned(ascii),
+            None => domain,
+        };
+
+        self.domain = Some(CookieStr::Concrete(domain));
     }
 
     /// Sets the expires field of `self` to `time`.
@@ -743,6 +863,33 @@ impl<'c> Cookie<'c> {
         self.set_expires(time::now() + twenty_years);
     }
 
+    /// Renders `self` as a `Set-Cookie` header value directly into `buf`,
+    /// returning the number of bytes written. The output is identical to that
+    /// of the `Display` implementation, but no allocation is performed.
+    ///
+    /// If the rendered cookie does not fit in `buf`, returns `BufferTooSmall`.
+    /// The contents of `buf` are unspecified in this case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cookie::Cookie;
+    ///
+    /// let c = Cookie::build("foo", "bar").path("/").finish();
+    ///
+    /// let mut buf = [0u8; 64];
+    /// let len = c.write_to(&mut buf).unwrap();
+    /// assert_eq!(&buf[..len], b"foo=bar; Path=/");
+    ///
+    /// let mut small = [0u8; 4];
+    /// assert!(c.write_to(&mut small).is_err());
+    /// ```
+    pub fn write_to(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let mut writer = SliceWriter::new(buf);
+        write!(writer, "{}", self).map_err(|_| BufferTooSmall)?;
+        Ok(writer.written())
+    }
+
     fn fmt_parameters(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(true) = self.http_only() {
             write!(f, "; HttpOnly")?;
@@ -967,6 +1114,34 @@ impl FromStr for Cookie<'static> {
     }
 }
 
+impl<N, V> From<(N, V)> for Cookie<'static>
+    where N: Into<Cow<'static, str>>,
+          V: Into<Cow<'static, str>>
+{
+    /// Creates a new `Cookie` from a `(name, value)` tuple. Equivalent to
+    /// [`Cookie::new`](#method.new).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar};
+    ///
+    /// let cookie = Cookie::from(("name", "value"));
+    /// assert_eq!(cookie.name_value(), ("name", "value"));
+    ///
+    /// let mut jar = CookieJar::new();
+    /// let pairs = vec![("a", "one".to_string()), ("b", "two".to_string())];
+    /// for cookie in pairs.into_iter().map(Cookie::from) {
+    ///     jar.add(cookie);
+    /// }
+    ///
+    /// assert_eq!(jar.get("b").map(|c| c.value()), Some("two"));
+    /// ```
+    fn from((name, value): (N, V)) -> Cookie<'static> {
+        Cookie::new(name, value)
+    }
+}
+
 impl<'a, 'b> PartialEq<Cookie<'b>> for Cookie<'a> {
     fn eq(&self, other: &Cookie<'b>) -> bool {
         let so_far_so_good = self.name() == other.name()
@@ -998,7 +1173,7 @@ impl<'a, 'b> PartialEq<Cookie<'b>> for Cookie<'a> {
 
 #[cfg(test)]
 mod tests {
-    use ::{Cookie, SameSite};
+    use ::{Cookie, SameSite, BufferTooSmall};
     use ::time::{strptime, Duration};
 
     #[test]
@@ -1046,6 +1221,22 @@ mod tests {
         assert_eq!(&cookie.to_string(), "foo=bar");
     }
 
+    #[test]
+    fn write_to() {
+        let cookie = Cookie::build("foo", "bar")
+            .path("/").http_only(true).finish();
+        let expected = cookie.to_string();
+
+        let mut buf = [0u8; 64];
+        let len = cookie.write_to(&mut buf).unwrap();
+        assert_eq!(&buf[..len], expected.as_bytes());
+
+        let exact = expected.len();
+        assert_eq!(cookie.write_to(&mut buf[..exact]), Ok(exact));
+        assert_eq!(cookie.write_to(&mut buf[..exact - 1]), Err(BufferTooSmall));
+        assert_eq!(cookie.write_to(&mut []), Err(BufferTooSmall));
+    }
+
     #[test]
     fn cookie_string_long_lifetimes() {
         let cookie_string = "bar=baz; Path=/subdir; HttpOnly; Domain=crates.io".to_owned();
@@ -1092,6 +1283,49 @@ mod tests {
         assert_eq!(domain, None);
     }
 
+    #[test]
+    fn pair_conversions() {
+        use std::borrow::Cow;
+
+        let cookie: Cookie = ("foo", String::from("bar")).into();
+        assert_eq!(cookie, Cookie::new("foo", "bar"));
+
+        let cookie_string = "bar=baz; Path=/subdir".to_owned();
+        let (name, value) = Cookie::parse(cookie_string.as_str()).unwrap().into_pair();
+        assert!(matches!((&name, &value), (Cow::Borrowed(_), Cow::Borrowed(_))));
+        assert_eq!((name, value), (Cow::Borrowed("bar"), Cow::Borrowed("baz")));
+
+        let (name, value) = Cookie::parse(cookie_string).unwrap().into_pair();
+        assert!(matches!((&name, &value), (Cow::Owned(_), Cow::Owned(_))));
+        assert_eq!((&*name, &*value), ("bar", "baz"));
+
+        let mut cookie = Cookie::parse("bar=baz").unwrap();
+        cookie.set_value("qux");
+        assert_eq!(cookie.into_pair(), (Cow::Borrowed("bar"), Cow::Borrowed("qux")));
+    }
+
+    #[test]
+    #[cfg(feature = "idna")]
+    fn idna_domain() {
+        let mut cookie = Cookie::new("foo", "bar");
+        cookie.set_domain("bücher.example");
+        assert_eq!(cookie.domain(), Some("xn--bcher-kva.example"));
+        assert_eq!(&cookie.to_string(), "foo=bar; Domain=xn--bcher-kva.example");
+
+        cookie.set_domain("xn--bcher-kva.example");
+        assert_eq!(cookie.domain(), Some("xn--bcher-kva.example"));
+
+        let cookie = Cookie::build("foo", "bar").domain("BÜCHER.example").finish();
+        assert_eq!(cookie.domain(), Some("xn--bcher-kva.example"));
+
+        let unicode = Cookie::parse("foo=bar; Domain=.bücher.example").unwrap();
+        let ascii = Cookie::parse("foo=bar; Domain=xn--bcher-kva.example").unwrap();
+        assert_eq!(unicode.domain(), Some("xn--bcher-kva.example"));
+        assert_eq!(unicode.domain_raw(), None);
+        assert_eq!(ascii.domain_raw(), Some("xn--bcher-kva.example"));
+        assert_eq!(unicode, ascii);
+    }
+
     #[test]
     #[cfg(feature = "percent-encode")]
     fn format_encoded() {
@@ -61,6 +61,86 @@ impl Error for ParseError {
     }
 }
 
+/// How nameless cookies in a `Cookie` header are handled by
+/// [`Cookie::parse_header`](struct.Cookie.html#method.parse_header).
+///
+/// Browsers treat both `=value` and a bare `value` segment in a `Cookie`
+/// header as a cookie whose name is empty and whose value is `value`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Nameless {
+    /// Nameless cookies are parsed as cookies with an empty name.
+    Accept,
+    /// Nameless cookies are ignored.
+    Skip,
+    /// Nameless cookies result in `ParseError::EmptyName`.
+    Reject,
+}
+
+/// Iterator over the cookies in a `Cookie` header.
+///
+/// A value of this type is returned by
+/// [`Cookie::parse_header`](struct.Cookie.html#method.parse_header). Each item
+/// is the result of parsing one `;`-separated segment of the header; empty
+/// segments are skipped.
+pub struct HeaderCookies<'c> {
+    segments: ::std::str::Split<'c, char>,
+    nameless: Nameless,
+}
+
+impl<'c> Iterator for HeaderCookies<'c> {
+    type Item = Result<Cookie<'c>, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Cookie<'c>, ParseError>> {
+        for segment in self.segments.by_ref() {
+            if segment.trim().is_empty() {
+                continue;
+            }
+
+            let (name, value) = match segment.find('=') {
+                Some(i) if !segment[..i].trim().is_empty() => {
+                    return Some(parse_cookie(segment, false));
+                }
+                Some(i) => (segment[..i].trim(), segment[(i + 1)..].trim()),
+                None => {
+                    let value = segment.trim();
+                    (&value[..0], value)
+                }
+            };
+
+            match self.nameless {
+                Nameless::Accept => return Some(Ok(nameless_cookie(segment, name, value))),
+                Nameless::Skip => continue,
+                Nameless::Reject => return Some(Err(ParseError::EmptyName)),
+            }
+        }
+
+        None
+    }
+}
+
+/// Creates a cookie whose name is the empty subslice `name` of `segment` and
+/// whose value is the subslice `value` of `segment`.
+fn nameless_cookie<'c>(segment: &'c str, name: &'c str, value: &'c str) -> Cookie<'c> {
+    let name_indexes = indexes_of(name, segment).expect("name sub");
+    let value_indexes = indexes_of(value, segment).expect("value sub");
+    Cookie {
+        cookie_string: Some(Cow::Borrowed(segment)),
+        name: CookieStr::Indexed(name_indexes.0, name_indexes.1),
+        value: CookieStr::Indexed(value_indexes.0, value_indexes.1),
+        expires: None,
+        max_age: None,
+        domain: None,
+        path: None,
+        secure: None,
+        http_only: None,
+        same_site: None
+    }
+}
+
+pub fn parse_header<'c>(s: &'c str, nameless: Nameless) -> HeaderCookies<'c> {
+    HeaderCookies { segments: s.split(';'), nameless }
+}
+
 fn indexes_of(needle: &str, haystack: &str) -> Option<(usize, usize)> {
     let haystack_start = haystack.as_ptr() as usize;
     let needle_start = needle.as_ptr() as usize;
@@ -169,6 +249,14 @@ fn parse_inner<'c>(s: &str, decode: bool) -> Result<Cookie<'c>, ParseError> {
                     domain = &domain[1..];
                 }
 
+                #[cfg(feature = "idna")]
+                {
+                    if let Some(ascii) = ::domain_to_ascii(domain) {
+                        cookie.domain = Some(CookieStr::Concrete(Cow::Owned(ascii)));
+                        continue;
+                    }
+                }
+
                 let (i, j) = indexes_of(domain, s).expect("domain sub");
                 cookie.domain = Some(CookieStr::Indexed(i, j));
             }
@@ -225,7 +313,7 @@ pub fn parse_cookie<'c, S>(cow: S, decode: bool) -> Result<Cookie<'c>, ParseErro
 
 #[cfg(test)]
 mod tests {
-    use ::{Cookie, SameSite};
+    use ::{Cookie, SameSite, ParseError};
     use ::time::{strptime, Duration};
 
     macro_rules! assert_eq_parse {
@@ -409,4 +497,40 @@ mod tests {
             .finish();
         assert_eq_parse!(format!(" foo=bar; Max-Age={:?}", max_seconds + 1), expected);
     }
+
+    #[test]
+    fn parse_header() {
+        use super::Nameless;
+
+        let header = "a=1; =2; 3 ;b = 4;;";
+        let cookies: Vec<_> = Cookie::parse_header(header, Nameless::Accept)
+            .map(|c| c.unwrap())
+            .collect();
+        let pairs: Vec<_> = cookies.iter().map(|c| c.name_value()).collect();
+        assert_eq!(pairs, vec![("a", "1"), ("", "2"), ("", "3"), ("b", "4")]);
+
+        let names: Vec<_> = Cookie::parse_header(header, Nameless::Skip)
+            .map(|c| c.unwrap().name().to_string())
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+
+        let results: Vec<_> = Cookie::parse_header(header, Nameless::Reject).collect();
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok() && results[3].is_ok());
+        assert_eq!(results[1].as_ref().unwrap_err(), &ParseError::EmptyName);
+        assert_eq!(results[2].as_ref().unwrap_err(), &ParseError::EmptyName);
+
+        let raw: Vec<_> = Cookie::parse_header(header, Nameless::Accept)
+            .map(|c| c.unwrap().value_raw())
+            .collect();
+        assert_eq!(raw, vec![Some("1"), Some("2"), Some("3"), Some("4")]);
+
+        let raw_names: Vec<_> = Cookie::parse_header(header, Nameless::Accept)
+            .map(|c| c.unwrap().name_raw())
+            .collect();
+        assert_eq!(raw_names, vec![Some("a"), Some(""), Some(""), Some("b")]);
+
+        assert_eq!(Cookie::parse_header("", Nameless::Reject).count(), 0);
+        assert_eq!(Cookie::parse_header(" ; ", Nameless::Reject).count(), 0);
+    }
 }
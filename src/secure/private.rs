@@ -3,12 +3,14 @@ use secure::ring::aead::{OpeningKey, SealingKey};
 use secure::ring::rand::{SecureRandom, SystemRandom};
 use secure::{base64, Key};
 
-use {Cookie, CookieJar};
+use {Cookie, CookieJar, ParentJar};
 
 // Keep these in sync, and keep the key len synced with the `private` docs as
 // well as the `KEYS_INFO` const in secure::Key.
 static ALGO: &'static Algorithm = &AES_256_GCM;
 const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+/// The length, in bytes, of the key used to encrypt private cookies.
 pub const KEY_LEN: usize = 32;
 
 /// A child cookie jar that provides authenticated encryption for its cookies.
@@ -19,18 +21,139 @@ pub const KEY_LEN: usize = 32;
 /// authenticity. In other words, clients cannot discover nor tamper with the
 /// contents of a cookie, nor can they fabricate cookie data.
 ///
+/// A `PrivateJar` is usually attached directly to a `CookieJar` via the
+/// [private](struct.CookieJar.html#method.private) method of `CookieJar`, but
+/// it can be attached to any [`ParentJar`](trait.ParentJar.html), including a
+/// [`ChildJar`](struct.ChildJar.html), with [`PrivateJar::new`](#method.new).
+///
 /// This type is only available when the `secure` feature is enabled.
-pub struct PrivateJar<'a> {
-    parent: &'a mut CookieJar,
+pub struct PrivateJar<'a, P: ?Sized + 'a = CookieJar> {
+    parent: &'a mut P,
     key: [u8; KEY_LEN]
 }
 
 impl<'a> PrivateJar<'a> {
+    /// The length, in bytes, of the random nonce prepended to every sealed
+    /// value before it is Base64 encoded.
+    pub const NONCE_LEN: usize = NONCE_LEN;
+
+    /// The length, in bytes, of the authentication tag appended to every sealed
+    /// value before it is Base64 encoded.
+    pub const TAG_LEN: usize = TAG_LEN;
+
+    /// The maximum combined size, in bytes, of a cookie's name and value that
+    /// user agents accept, per RFC 6265bis. This is the target size used by
+    /// [`max_plaintext_len_default`](#method.max_plaintext_len_default).
+    ///
+    /// Attributes such as `Path`, `Domain`, and `Expires` are not counted.
+    /// RFC 6265 Section 6.1 instead applies its 4096 byte minimum to the name,
+    /// value, and attributes together, so a cookie sealed at this size that
+    /// also sets attributes exceeds what RFC 6265 requires browsers to accept.
+    pub const MAX_COOKIE_SIZE: usize = 4096;
+
+    /// Returns the length of the cookie value produced by sealing a value that
+    /// is `plaintext_len` bytes long: the nonce, the encrypted value, and the
+    /// tag, Base64 encoded with padding.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{CookieJar, Cookie, Key, PrivateJar};
+    ///
+    /// let key = Key::generate();
+    /// let mut jar = CookieJar::new();
+    /// jar.private(&key).add(Cookie::new("name", "value"));
+    ///
+    /// let sealed_len = jar.get("name").unwrap().value().len();
+    /// assert_eq!(sealed_len, PrivateJar::sealed_len("value".len()));
+    /// ```
+    #[allow(unknown_lints, clippy::manual_div_ceil)]
+    pub fn sealed_len(plaintext_len: usize) -> usize {
+        let sealed = NONCE_LEN + plaintext_len + TAG_LEN;
+        (sealed + 2) / 3 * 4
+    }
+
+    /// Returns the length of the largest value that, once sealed, fits in a
+    /// cookie named `name` whose name and value together are at most
+    /// `cookie_size` bytes long. Returns `None` if not even an empty value
+    /// fits. The cookie's attributes are not counted against `cookie_size`.
+    /// To target the RFC 6265bis limit, use
+    /// [`max_plaintext_len_default`](#method.max_plaintext_len_default).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::PrivateJar;
+    ///
+    /// let max = PrivateJar::max_plaintext_len("session", 1024).unwrap();
+    /// assert!("session".len() + 1 + PrivateJar::sealed_len(max) <= 1024);
+    /// assert!("session".len() + 1 + PrivateJar::sealed_len(max + 1) > 1024);
+    ///
+    /// assert_eq!(PrivateJar::max_plaintext_len("session", 30), None);
+    /// ```
+    pub fn max_plaintext_len(name: &str, cookie_size: usize) -> Option<usize> {
+        // The value budget excludes the name and the `=` separator.
+        let value_len = cookie_size.checked_sub(name.len() + 1)?;
+        (value_len / 4 * 3).checked_sub(NONCE_LEN + TAG_LEN)
+    }
+
+    /// Returns the length of the largest value that, once sealed, fits in a
+    /// cookie named `name` whose name and value together are at most
+    /// `PrivateJar::MAX_COOKIE_SIZE` (4096) bytes long. Returns `None` if not
+    /// even an empty value fits. As with `max_plaintext_len`, the cookie's
+    /// attributes are not counted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::PrivateJar;
+    ///
+    /// let max = PrivateJar::max_plaintext_len_default("session").unwrap();
+    /// assert_eq!(Some(max), PrivateJar::max_plaintext_len("session", 4096));
+    /// assert!("session".len() + 1 + PrivateJar::sealed_len(max) <= 4096);
+    /// ```
+    pub fn max_plaintext_len_default(name: &str) -> Option<usize> {
+        Self::max_plaintext_len(name, Self::MAX_COOKIE_SIZE)
+    }
+
+}
+
+impl<'a, P: ParentJar + ?Sized> PrivateJar<'a, P> {
     /// Creates a new child `PrivateJar` with parent `parent` and key `key`.
-    /// This method is typically called indirectly via the `signed` method of
-    /// `CookieJar`.
-    #[doc(hidden)]
-    pub fn new(parent: &'a mut CookieJar, key: &Key) -> PrivateJar<'a> {
+    /// To attach a private jar directly to a `CookieJar`, the
+    /// [private](struct.CookieJar.html#method.private) method of `CookieJar`
+    /// can be used instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar, Key, PrivateJar, Transform};
+    ///
+    /// struct Legacy;
+    ///
+    /// impl Transform for Legacy {
+    ///     fn encode(&self, mut cookie: Cookie<'static>) -> Cookie<'static> {
+    ///         let value = format!("v1:{}", cookie.value());
+    ///         cookie.set_value(value);
+    ///         cookie
+    ///     }
+    ///
+    ///     fn decode(&self, mut cookie: Cookie<'static>) -> Option<Cookie<'static>> {
+    ///         let value = cookie.value().trim_start_matches("v1:").to_string();
+    ///         cookie.set_value(value);
+    ///         Some(cookie)
+    ///     }
+    /// }
+    ///
+    /// let key = Key::generate();
+    /// let mut jar = CookieJar::new();
+    /// let mut legacy = jar.child(Legacy);
+    /// PrivateJar::new(&mut legacy, &key).add(Cookie::new("name", "value"));
+    /// assert_eq!(PrivateJar::new(&mut legacy, &key).get("name").unwrap().value(), "value");
+    ///
+    /// assert!(jar.get("name").unwrap().value().starts_with("v1:"));
+    /// ```
+    pub fn new(parent: &'a mut P, key: &Key) -> PrivateJar<'a, P> {
         let mut key_array = [0u8; KEY_LEN];
         key_array.copy_from_slice(key.encryption());
         PrivateJar { parent: parent, key: key_array }
@@ -78,8 +201,7 @@ impl<'a> PrivateJar<'a> {
     /// assert_eq!(private_jar.get("name").unwrap().value(), "value");
     /// ```
     pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
-        if let Some(cookie_ref) = self.parent.get(name) {
-            let mut cookie = cookie_ref.clone();
+        if let Some(mut cookie) = self.parent.get(name) {
             if let Ok(value) = self.unseal(name, cookie.value()) {
                 cookie.set_value(value);
                 return Some(cookie);
@@ -150,7 +272,7 @@ impl<'a> PrivateJar<'a> {
             let key = SealingKey::new(ALGO, &self.key).expect("sealing key creation");
 
             // Create a vec to hold the [nonce | cookie value | overhead].
-            let overhead = ALGO.tag_len();
+            let overhead = TAG_LEN;
             let cookie_val = cookie.value().as_bytes();
             data = vec![0; NONCE_LEN + cookie_val.len() + overhead];
 
@@ -201,6 +323,24 @@ impl<'a> PrivateJar<'a> {
     }
 }
 
+impl<'a, P: ParentJar + ?Sized> ParentJar for PrivateJar<'a, P> {
+    fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        PrivateJar::get(self, name)
+    }
+
+    fn add(&mut self, cookie: Cookie<'static>) {
+        PrivateJar::add(self, cookie)
+    }
+
+    fn add_original(&mut self, cookie: Cookie<'static>) {
+        PrivateJar::add_original(self, cookie)
+    }
+
+    fn remove(&mut self, cookie: Cookie<'static>) {
+        PrivateJar::remove(self, cookie)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use {CookieJar, Cookie, Key};
@@ -218,4 +358,35 @@ mod test {
         let mut jar = CookieJar::new();
         assert_secure_behaviour!(jar, jar.private(&key));
     }
+
+    #[test]
+    fn sealing_overhead() {
+        use super::{ALGO, TAG_LEN};
+        use PrivateJar;
+
+        assert_eq!(ALGO.tag_len(), TAG_LEN);
+
+        let key = Key::generate();
+        let mut jar = CookieJar::new();
+        for len in 0..10 {
+            let value = "a".repeat(len);
+            jar.private(&key).add(Cookie::new("name", value));
+            let sealed = jar.get("name").unwrap().value().len();
+            assert_eq!(sealed, PrivateJar::sealed_len(len));
+        }
+
+        for &size in &[0, 5, 30, 44] {
+            assert_eq!(PrivateJar::max_plaintext_len("name", size), None);
+        }
+
+        for &size in &[45, 46, 100, 4096] {
+            let max = PrivateJar::max_plaintext_len("name", size).unwrap();
+            assert!(5 + PrivateJar::sealed_len(max) <= size);
+            assert!(5 + PrivateJar::sealed_len(max + 1) > size);
+        }
+
+        assert_eq!(PrivateJar::max_plaintext_len("name", 45), Some(2));
+        assert_eq!(PrivateJar::max_plaintext_len_default("name"),
+                   PrivateJar::max_plaintext_len("name", 4096));
+    }
 }
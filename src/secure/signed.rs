@@ -2,12 +2,13 @@ use secure::ring::digest::{SHA256, Algorithm};
 use secure::ring::hmac::{SigningKey, sign, verify_with_own_key as verify};
 use secure::{base64, Key};
 
-use {Cookie, CookieJar};
+use {Cookie, CookieJar, ParentJar};
 
 // Keep these in sync, and keep the key len synced with the `signed` docs as
 // well as the `KEYS_INFO` const in secure::Key.
 static HMAC_DIGEST: &'static Algorithm = &SHA256;
 const BASE64_DIGEST_LEN: usize = 44;
+/// The length, in bytes, of the key used to sign cookies.
 pub const KEY_LEN: usize = 32;
 
 /// A child cookie jar that authenticates its cookies.
@@ -18,18 +19,35 @@ pub const KEY_LEN: usize = 32;
 /// a cookie nor can they fabricate cookie values, but the data is visible in
 /// plaintext.
 ///
+/// A `SignedJar` is usually attached directly to a `CookieJar` via the
+/// [signed](struct.CookieJar.html#method.signed) method of `CookieJar`, but it
+/// can be attached to any [`ParentJar`](trait.ParentJar.html), including a
+/// [`ChildJar`](struct.ChildJar.html), with [`SignedJar::new`](#method.new).
+///
 /// This type is only available when the `secure` feature is enabled.
-pub struct SignedJar<'a> {
-    parent: &'a mut CookieJar,
+pub struct SignedJar<'a, P: ?Sized + 'a = CookieJar> {
+    parent: &'a mut P,
     key: SigningKey
 }
 
-impl<'a> SignedJar<'a> {
-    /// Creates a new child `SignedJar` with parent `parent` and key `key`. This
-    /// method is typically called indirectly via the `signed` method of
-    /// `CookieJar`.
-    #[doc(hidden)]
-    pub fn new(parent: &'a mut CookieJar, key: &Key) -> SignedJar<'a> {
+impl<'a, P: ParentJar + ?Sized> SignedJar<'a, P> {
+    /// Creates a new child `SignedJar` with parent `parent` and key `key`. To
+    /// attach a signed jar directly to a `CookieJar`, the
+    /// [signed](struct.CookieJar.html#method.signed) method of `CookieJar` can
+    /// be used instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cookie::{Cookie, CookieJar, Key, SignedJar};
+    ///
+    /// let key = Key::generate();
+    /// let mut jar = CookieJar::new();
+    /// let mut private = jar.private(&key);
+    /// SignedJar::new(&mut private, &key).add(Cookie::new("name", "value"));
+    /// assert_eq!(SignedJar::new(&mut private, &key).get("name").unwrap().value(), "value");
+    /// ```
+    pub fn new(parent: &'a mut P, key: &Key) -> SignedJar<'a, P> {
         SignedJar { parent: parent, key: SigningKey::new(HMAC_DIGEST, key.signing()) }
     }
 
@@ -68,8 +86,7 @@ impl<'a> SignedJar<'a> {
     /// assert_eq!(signed_jar.get("name").unwrap().value(), "value");
     /// ```
     pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
-        if let Some(cookie_ref) = self.parent.get(name) {
-            let mut cookie = cookie_ref.clone();
+        if let Some(mut cookie) = self.parent.get(name) {
             if let Ok(value) = self.verify(cookie.value()) {
                 cookie.set_value(value);
                 return Some(cookie);
@@ -162,6 +179,24 @@ impl<'a> SignedJar<'a> {
     }
 }
 
+impl<'a, P: ParentJar + ?Sized> ParentJar for SignedJar<'a, P> {
+    fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        SignedJar::get(self, name)
+    }
+
+    fn add(&mut self, cookie: Cookie<'static>) {
+        SignedJar::add(self, cookie)
+    }
+
+    fn add_original(&mut self, cookie: Cookie<'static>) {
+        SignedJar::add_original(self, cookie)
+    }
+
+    fn remove(&mut self, cookie: Cookie<'static>) {
+        SignedJar::remove(self, cookie)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use {CookieJar, Cookie, Key};
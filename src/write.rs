@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned when a cookie does not fit in a caller-provided buffer.
+///
+/// This error is returned by
+/// [`Cookie::write_to`](struct.Cookie.html#method.write_to) and
+/// [`CookieJar::write_delta_to`](struct.CookieJar.html#method.write_delta_to)
+/// when the rendered output is longer than the buffer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BufferTooSmall;
+
+impl BufferTooSmall {
+    /// Returns a description of this error as a string
+    pub fn as_str(&self) -> &'static str {
+        "the buffer is too small to hold the rendered cookie"
+    }
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Error for BufferTooSmall {
+    fn description(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// A `fmt::Write` implementation that copies its output into a fixed byte
+/// buffer, failing instead of allocating once the buffer is full.
+pub struct SliceWriter<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> SliceWriter<'b> {
+    /// Creates a writer that starts writing at the beginning of `buf`.
+    pub fn new(buf: &'b mut [u8]) -> SliceWriter<'b> {
+        SliceWriter { buf, len: 0 }
+    }
+
+    /// The number of bytes written into the buffer so far.
+    pub fn written(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'b> fmt::Write for SliceWriter<'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}